@@ -0,0 +1,232 @@
+/// Filename:    ini_config.rs
+/// Author:      Matt Arriola
+/// Description: A high-level section/key lookup wrapper over the parsed IniFile tree
+
+pub mod ini_config {
+    use std::collections::HashMap;
+
+    use ini_format::ini_format::{IniFile, Entry, Section, SectionEntry};
+    use reader::reader::parse_list;
+
+    /// A read-only view onto a single section, handed out by `IniConfig::section`.
+    pub struct SectionView<'a> {
+        section: &'a Section,
+    }
+
+    impl<'a> SectionView<'a> {
+        pub fn get (&self, key: &str) -> Option<&'a str> {
+            for sub_entry in self.section.entries.iter() {
+                match sub_entry {
+                    &SectionEntry::Key(ref name, ref value) if name.as_slice() == key =>
+                        return Some(value.as_slice()),
+                    _ => (),
+                }
+            }
+
+            None
+        }
+
+        pub fn keys (&self) -> Box<Iterator<Item=&'a str> + 'a> {
+            box self.section.entries.iter().filter_map(|sub_entry| match sub_entry {
+                &SectionEntry::Key(ref name, _) => Some(name.as_slice()),
+                &SectionEntry::Comment(_)       => None,
+            })
+        }
+    }
+
+    /// Maps a section name down to the `(section_pos, key name -> key_pos)` of every section
+    /// sharing that name, in tree order, so a lookup needs neither a linear rescan of `ini` for
+    /// the section nor risks pairing one occurrence's key positions with another same-named
+    /// occurrence's position. `get`/`section` use the first occurrence, matching the old
+    /// `section_index` scan's "first match wins" behavior.
+    pub struct IniConfig {
+        pub ini: IniFile,
+        index: Option<HashMap<String, Vec<(uint, HashMap<String, uint>)>>>,
+    }
+
+    impl IniConfig {
+        pub fn new (ini: IniFile) -> IniConfig {
+            IniConfig { ini: ini, index: None }
+        }
+
+        fn ensure_index (&mut self) {
+            if self.index.is_some() {
+                return;
+            }
+
+            let mut index = HashMap::<String, Vec<(uint, HashMap<String, uint>)>>::new();
+
+            for (section_pos, entry) in self.ini.iter().enumerate() {
+                match entry {
+                    &Entry::Section(ref section) => {
+                        let mut keys = HashMap::<String, uint>::new();
+
+                        for (key_pos, sub_entry) in section.entries.iter().enumerate() {
+                            match sub_entry {
+                                &SectionEntry::Key(ref name, _) => {
+                                    keys.insert(name.clone(), key_pos);
+                                },
+                                &SectionEntry::Comment(_) => (),
+                            }
+                        }
+
+                        if !index.contains_key(&section.name) {
+                            index.insert(section.name.clone(), Vec::new());
+                        }
+                        index.get_mut(&section.name).unwrap().push((section_pos, keys));
+                    },
+                    &Entry::Comment(_) => (),
+                }
+            }
+
+            self.index = Some(index);
+        }
+
+        /// Linear scan used only by `set`, which needs the position of a section to mutate (or
+        /// to learn there isn't one yet) rather than the indexed, read-only lookup `get`/`section`
+        /// use.
+        fn section_index (&self, name: &str) -> Option<uint> {
+            for (pos, entry) in self.ini.iter().enumerate() {
+                match entry {
+                    &Entry::Section(ref section) if section.name.as_slice() == name => return Some(pos),
+                    _ => (),
+                }
+            }
+
+            None
+        }
+
+        pub fn get (&mut self, section: &str, key: &str) -> Option<&str> {
+            self.ensure_index();
+
+            match self.index.as_ref().unwrap().get(section) {
+                None => None,
+                Some(occurrences) => match occurrences.first() {
+                    None => None,
+                    Some(&(section_pos, ref keys)) => match keys.get(key) {
+                        None => None,
+                        Some(&key_pos) => match &self.ini[section_pos] {
+                            &Entry::Section(ref section) => match &section.entries[key_pos] {
+                                &SectionEntry::Key(_, ref value) => Some(value.as_slice()),
+                                &SectionEntry::Comment(_) => None,
+                            },
+                            &Entry::Comment(_) => None,
+                        },
+                    },
+                },
+            }
+        }
+
+        /// Reads `section`/`key` as a list, e.g. a value parsed from repeated `key = ...` lines
+        /// or an inline `(a, b, c)`/comma-separated value under `IniReader`'s list mode. Returns
+        /// an empty `Vec` if the key isn't present.
+        pub fn get_all (&mut self, section: &str, key: &str) -> Vec<String> {
+            match self.get(section, key) {
+                Some(value) => parse_list(value),
+                None => Vec::new(),
+            }
+        }
+
+        pub fn section (&mut self, name: &str) -> Option<SectionView> {
+            self.ensure_index();
+
+            let section_pos = match self.index.as_ref().unwrap().get(name) {
+                None => return None,
+                Some(occurrences) => match occurrences.first() {
+                    None => return None,
+                    Some(&(pos, _)) => pos,
+                },
+            };
+
+            match &self.ini[section_pos] {
+                &Entry::Section(ref section) => Some(SectionView { section: section }),
+                &Entry::Comment(_) => None,
+            }
+        }
+
+        pub fn sections<'a> (&'a self) -> Box<Iterator<Item=&'a str> + 'a> {
+            box self.ini.iter().filter_map(|entry| match entry {
+                &Entry::Section(ref section) => Some(section.name.as_slice()),
+                &Entry::Comment(_)           => None,
+            })
+        }
+
+        /// Inserts `key = value` into `section`, updating the matching `SectionEntry::Key` in
+        /// place if one already exists, appending a new one otherwise, and creating the section
+        /// itself if it isn't present yet. The index is rebuilt on the next lookup.
+        pub fn set (&mut self, section: &str, key: &str, value: &str) {
+            let section_pos = match self.section_index(section) {
+                Some(pos) => pos,
+                None => {
+                    self.ini.push(Entry::Section(Section {
+                        name: section.to_string(),
+                        entries: Vec::<SectionEntry>::new(),
+                    }));
+                    self.ini.len() - 1
+                },
+            };
+
+            match self.ini[section_pos] {
+                Entry::Section(ref mut section_ref) => {
+                    let mut found = false;
+
+                    for sub_entry in section_ref.entries.iter_mut() {
+                        match sub_entry {
+                            &mut SectionEntry::Key(ref name, ref mut existing) if name.as_slice() == key => {
+                                *existing = value.to_string();
+                                found = true;
+                                break;
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    if !found {
+                        section_ref.entries.push(SectionEntry::Key(key.to_string(), value.to_string()));
+                    }
+                },
+                Entry::Comment(_) => (),
+            }
+
+            // The index was built against the tree's old shape; the next get()/section() call
+            // will rebuild it lazily.
+            self.index = None;
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        fn section (name: &str, entries: Vec<SectionEntry>) -> Entry {
+            Entry::Section(Section { name: name.to_string(), entries: entries })
+        }
+
+        /// Regression test for c328399: the index used to collapse every section sharing a name
+        /// into one key map and discard section_pos, so a key present only in a later occurrence
+        /// (here, db's "port") resolved its position against the *first* occurrence's entries --
+        /// either reading the wrong value or indexing past the end of a shorter section.
+        #[test]
+        fn get_resolves_against_the_matching_occurrence_of_a_repeated_section_name () {
+            let ini_file = vec![
+                section("db", vec![SectionEntry::Key("host".to_string(), "first".to_string())]),
+                section("db", vec![
+                    SectionEntry::Key("host".to_string(), "second".to_string()),
+                    SectionEntry::Key("port".to_string(), "5432".to_string()),
+                ]),
+            ];
+
+            let mut config = IniConfig::new(ini_file);
+
+            assert_eq!(config.get("db", "host"), Some("first"));
+            assert_eq!(config.get("db", "port"), None);
+        }
+
+        #[test]
+        fn get_and_set_round_trip_a_value () {
+            let mut config = IniConfig::new(Vec::new());
+            config.set("section", "key", "value");
+            assert_eq!(config.get("section", "key"), Some("value"));
+        }
+    }
+}
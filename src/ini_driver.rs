@@ -1,4 +1,7 @@
 #![feature(macro_rules)]
+#![feature(box_syntax)]
+
+extern crate serde;
 
 /// Filename:    ini_driver.rs
 /// Author:      Matt Arriola
@@ -15,9 +18,15 @@ use reader::reader::ParseResult;
 mod ini_format;
 mod writer;
 mod reader;
+mod ini_serde;
+mod ini_config;
 
 fn main () {
-    let mut ini_reader = IniReader::new("test.ini".to_string());
+    let mut ini_reader = match IniReader::from_file("test.ini".to_string()) {
+        Ok(reader) => reader,
+        Err(e)     => panic!("could not open test.ini: {}", e),
+    };
+
     println!("Parse {}", match ini_reader.parse() {
         ParseResult::Ok     => "successful".to_string(),
         ParseResult::Err(e) => format!("failed ({})", e)
@@ -0,0 +1,397 @@
+/// Filename:    ini_serde.rs
+/// Author:      Matt Arriola
+/// Description: serde Serializer/Deserializer bridging Rust structs and the IniFile tree
+///
+/// A top-level `Entry::Section` deserializes as a map entry whose value is itself a map of
+/// `SectionEntry::Key(name, value)` pairs; keys that appear before any `[section]` header are
+/// gathered into a synthetic section (`DEFAULT_SECTION`), so a config struct reads them through a
+/// field of that name. `Serializer` names each section after the nested struct's Rust type name,
+/// not after the field, and writes no keys outside a section.
+
+pub mod ini_serde {
+    use serde::de;
+    use serde::de::{Deserialize, Deserializer as SerdeDeserializer, Visitor};
+    use serde::ser;
+    use serde::ser::{Serialize, Serializer as SerdeSerializer};
+
+    use ini_format::ini_format::{IniFile, Entry, Section, SectionEntry};
+    use reader::reader::{IniReader, ParseResult};
+    use writer::writer::write_ini_string;
+
+    /// Section that catches keys written before any `[section]` header.
+    static DEFAULT_SECTION: &'static str = "default";
+
+    pub enum Error {
+        Parse(String),
+        Custom(String),
+    }
+
+    impl de::Error for Error {
+        fn custom<T: ::std::fmt::Display> (msg: T) -> Error {
+            Error::Custom(format!("{}", msg))
+        }
+    }
+
+    impl ser::Error for Error {
+        fn custom<T: ::std::fmt::Display> (msg: T) -> Error {
+            Error::Custom(format!("{}", msg))
+        }
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // Deserializer
+
+    /// Walks a parsed `IniFile`, handing sections to `visit_map` and scalar values to whichever
+    /// `visit_*` the target type asks for.
+    pub struct Deserializer {
+        ini: IniFile,
+    }
+
+    impl Deserializer {
+        pub fn from_ini_file (ini: IniFile) -> Deserializer {
+            Deserializer { ini: ini }
+        }
+    }
+
+    impl SerdeDeserializer for Deserializer {
+        type Error = Error;
+
+        fn deserialize<V: Visitor> (&mut self, mut visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_map(SectionMapVisitor { entries: self.ini.iter(), pending: None })
+        }
+    }
+
+    struct SectionMapVisitor<'a> {
+        entries: ::std::slice::Iter<'a, Entry>,
+        pending: Option<&'a Section>,
+    }
+
+    impl<'a> de::MapVisitor for SectionMapVisitor<'a> {
+        type Error = Error;
+
+        fn visit_key<K: Deserialize> (&mut self) -> Result<Option<K>, Error> {
+            loop {
+                match self.entries.next() {
+                    None => return Ok(None),
+                    Some(&Entry::Comment(_)) => continue,
+                    Some(&Entry::Section(ref section)) => {
+                        self.pending = Some(section);
+                        let name = if section.name.is_empty() { DEFAULT_SECTION.to_string() }
+                                   else { section.name.clone() };
+                        return Deserialize::deserialize(&mut KeyDeserializer::new(name)).map(Some);
+                    },
+                }
+            }
+        }
+
+        fn visit_value<V: Deserialize> (&mut self) -> Result<V, Error> {
+            match self.pending.take() {
+                Some(section) => Deserialize::deserialize(&mut KeyMapDeserializer::new(section)),
+                None => Err(Error::Custom("visit_value called before visit_key".to_string())),
+            }
+        }
+    }
+
+    /// Deserializes the `SectionEntry::Key` pairs of a single section into a struct or map.
+    struct KeyMapDeserializer<'a> {
+        entries: ::std::slice::Iter<'a, SectionEntry>,
+        pending: Option<&'a str>,
+    }
+
+    impl<'a> KeyMapDeserializer<'a> {
+        fn new (section: &'a Section) -> KeyMapDeserializer<'a> {
+            KeyMapDeserializer { entries: section.entries.iter(), pending: None }
+        }
+    }
+
+    impl<'a> SerdeDeserializer for KeyMapDeserializer<'a> {
+        type Error = Error;
+
+        fn deserialize<V: Visitor> (&mut self, mut visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_map(self)
+        }
+    }
+
+    impl<'a> de::MapVisitor for KeyMapDeserializer<'a> {
+        type Error = Error;
+
+        fn visit_key<K: Deserialize> (&mut self) -> Result<Option<K>, Error> {
+            loop {
+                match self.entries.next() {
+                    None => return Ok(None),
+                    Some(&SectionEntry::Comment(_)) => continue,
+                    Some(&SectionEntry::Key(ref name, ref value)) => {
+                        self.pending = Some(value.as_slice());
+                        return Deserialize::deserialize(&mut KeyDeserializer::new(name.clone())).map(Some);
+                    },
+                }
+            }
+        }
+
+        fn visit_value<V: Deserialize> (&mut self) -> Result<V, Error> {
+            match self.pending.take() {
+                Some(value) => Deserialize::deserialize(&mut ScalarDeserializer::new(value.to_string())),
+                None => Err(Error::Custom("visit_value called before visit_key".to_string())),
+            }
+        }
+    }
+
+    /// Deserializes a section or key *name* as a field identifier. Unlike `ScalarDeserializer`,
+    /// this always calls `visit_str` -- a section or key literally named `"true"` or `"7"` must
+    /// still dispatch as a string to the derived field-identifier visitor, not get sniffed into a
+    /// `bool`/`i64` the way a leaf value would be.
+    struct KeyDeserializer {
+        name: String,
+    }
+
+    impl KeyDeserializer {
+        fn new (name: String) -> KeyDeserializer {
+            KeyDeserializer { name: name }
+        }
+    }
+
+    impl SerdeDeserializer for KeyDeserializer {
+        type Error = Error;
+
+        fn deserialize<V: Visitor> (&mut self, mut visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_str(self.name.as_slice())
+        }
+    }
+
+    /// Deserializes a single string by the shape of its text alone: a value that parses as a
+    /// `bool` goes to `visit_bool`, else one that parses as an `i64` to `visit_i64`, else one that
+    /// parses as an `f64` to `visit_f64`, and anything else to `visit_str`. The target type is not
+    /// consulted, so a numeric-looking value reaches a `String` target through `visit_i64`/`visit_f64`.
+    struct ScalarDeserializer {
+        value: String,
+    }
+
+    impl ScalarDeserializer {
+        fn new (value: String) -> ScalarDeserializer {
+            ScalarDeserializer { value: value }
+        }
+    }
+
+    impl SerdeDeserializer for ScalarDeserializer {
+        type Error = Error;
+
+        fn deserialize<V: Visitor> (&mut self, mut visitor: V) -> Result<V::Value, Error> {
+            if let Ok(b) = self.value.parse::<bool>() {
+                return visitor.visit_bool(b);
+            }
+            if let Ok(i) = self.value.parse::<i64>() {
+                return visitor.visit_i64(i);
+            }
+            if let Ok(f) = self.value.parse::<f64>() {
+                return visitor.visit_f64(f);
+            }
+            visitor.visit_str(self.value.as_slice())
+        }
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // Serializer
+
+    /// Walks a struct's fields, emitting one `Entry::Section` per nested struct and one
+    /// `SectionEntry::Key` per scalar field. Only structs open a section; map values are not
+    /// handled. The outermost `visit_struct` call (the config type
+    /// itself) does not wrap its fields in a section of their own -- it just walks them, mirroring
+    /// how `Deserializer` treats the outermost type as a map of sections rather than a section.
+    /// Each field's value re-enters this same `Serializer`, so a nested struct field triggers its
+    /// own `visit_struct` call (and so its own `Entry::Section`), while a genuinely scalar field
+    /// lands in `pending_scalar` for `visit_struct_elt` to store as a key in the current section.
+    pub struct Serializer {
+        ini: IniFile,
+        current: Option<Section>,
+        depth: uint,
+        pending_scalar: Option<String>,
+    }
+
+    impl Serializer {
+        pub fn new () -> Serializer {
+            Serializer { ini: Vec::new(), current: None, depth: 0, pending_scalar: None }
+        }
+
+        pub fn into_ini_file (self) -> IniFile {
+            self.ini
+        }
+    }
+
+    impl SerdeSerializer for Serializer {
+        type Error = Error;
+
+        fn visit_struct<F> (&mut self, name: &str, mut visit_fields: F) -> Result<(), Error>
+            where F: FnMut(&mut Serializer) -> Result<(), Error> {
+            self.depth += 1;
+
+            if self.depth == 1 {
+                try!(visit_fields(self));
+                self.depth -= 1;
+                return Ok(());
+            }
+
+            let previous = self.current.take();
+            self.current = Some(Section { name: name.to_string(), entries: Vec::new() });
+            try!(visit_fields(self));
+            let section = self.current.take().unwrap();
+            self.ini.push(Entry::Section(section));
+            self.current = previous;
+            self.depth -= 1;
+            Ok(())
+        }
+
+        fn visit_struct_elt<T: Serialize> (&mut self, name: &str, value: T) -> Result<(), Error> {
+            self.pending_scalar = None;
+            try!(value.serialize(self));
+
+            match self.pending_scalar.take() {
+                Some(rendered) => match self.current {
+                    Some(ref mut section) => {
+                        section.entries.push(SectionEntry::Key(name.to_string(), rendered));
+                        Ok(())
+                    },
+                    None => Err(Error::Custom(format!("key '{}' written outside of a section", name))),
+                },
+                // value.serialize(self) was itself a nested struct; it already pushed its
+                // own Entry::Section above, so there's nothing left to store here.
+                None => Ok(()),
+            }
+        }
+
+        fn visit_bool (&mut self, v: bool) -> Result<(), Error> { self.pending_scalar = Some(v.to_string()); Ok(()) }
+        fn visit_i64 (&mut self, v: i64) -> Result<(), Error> { self.pending_scalar = Some(v.to_string()); Ok(()) }
+        fn visit_f64 (&mut self, v: f64) -> Result<(), Error> { self.pending_scalar = Some(v.to_string()); Ok(()) }
+        fn visit_str (&mut self, v: &str) -> Result<(), Error> { self.pending_scalar = Some(v.to_string()); Ok(()) }
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // Entry points
+
+    /// Parses `text` as an INI document and deserializes it into `T`.
+    pub fn from_str<T: Deserialize> (text: &str) -> Result<T, Error> {
+        let mut ini_reader = IniReader::from_str(text);
+
+        match ini_reader.parse() {
+            ParseResult::Ok       => Deserialize::deserialize(&mut Deserializer::from_ini_file(ini_reader.ini)),
+            ParseResult::Err(e)   => Err(Error::Parse(e.to_string())),
+            ParseResult::StepOk(_) => Err(Error::Parse("unexpected partial parse".to_string())),
+        }
+    }
+
+    /// Serializes `value` into an INI document.
+    pub fn to_string<T: Serialize> (value: &T) -> Result<String, Error> {
+        let mut serializer = Serializer::new();
+        try!(value.serialize(&mut serializer));
+        Ok(write_ini_string(&serializer.into_ini_file()))
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        /// Records which `visit_*` a deserializer actually dispatched to, so tests can tell
+        /// "sniffed as a bool/i64" apart from "passed through as a string" without needing a
+        /// full derived target type.
+        enum Captured {
+            Bool(bool),
+            I64(i64),
+            Str(String),
+        }
+
+        struct CaptureVisitor;
+
+        impl Visitor for CaptureVisitor {
+            type Value = Captured;
+
+            fn visit_bool (&mut self, v: bool) -> Result<Captured, Error> { Ok(Captured::Bool(v)) }
+            fn visit_i64 (&mut self, v: i64) -> Result<Captured, Error> { Ok(Captured::I64(v)) }
+            fn visit_str (&mut self, v: &str) -> Result<Captured, Error> { Ok(Captured::Str(v.to_string())) }
+        }
+
+        #[test]
+        fn scalar_deserializer_sniffs_typed_values () {
+            match ScalarDeserializer::new("true".to_string()).deserialize(CaptureVisitor) {
+                Ok(Captured::Bool(true)) => (),
+                _ => panic!("expected ScalarDeserializer to dispatch 'true' via visit_bool"),
+            }
+
+            match ScalarDeserializer::new("7".to_string()).deserialize(CaptureVisitor) {
+                Ok(Captured::I64(7)) => (),
+                _ => panic!("expected ScalarDeserializer to dispatch '7' via visit_i64"),
+            }
+
+            match ScalarDeserializer::new("hello".to_string()).deserialize(CaptureVisitor) {
+                Ok(Captured::Str(ref s)) if s.as_slice() == "hello" => (),
+                _ => panic!("expected ScalarDeserializer to fall back to visit_str"),
+            }
+        }
+
+        /// Regression test: before KeyDeserializer existed, section/key names went through
+        /// ScalarDeserializer too, so a name that looked numeric would dispatch via visit_i64
+        /// instead of the visit_str a derived field-identifier visitor actually implements.
+        #[test]
+        fn key_deserializer_always_dispatches_via_visit_str () {
+            match KeyDeserializer::new("7".to_string()).deserialize(CaptureVisitor) {
+                Ok(Captured::Str(ref s)) if s.as_slice() == "7" => (),
+                _ => panic!("expected KeyDeserializer to dispatch '7' via visit_str, not visit_i64"),
+            }
+        }
+
+        struct Db {
+            host: String,
+        }
+
+        impl Serialize for Db {
+            fn serialize<S: SerdeSerializer> (&self, serializer: &mut S) -> Result<(), S::Error> {
+                serializer.visit_struct("Db", |s| {
+                    try!(s.visit_struct_elt("host", self.host.clone()));
+                    Ok(())
+                })
+            }
+        }
+
+        struct Config {
+            db: Db,
+        }
+
+        impl Serialize for Config {
+            fn serialize<S: SerdeSerializer> (&self, serializer: &mut S) -> Result<(), S::Error> {
+                serializer.visit_struct("Config", |s| {
+                    try!(s.visit_struct_elt("db", Db { host: self.db.host.clone() }));
+                    Ok(())
+                })
+            }
+        }
+
+        /// Regression test for the Serializer fix: a nested struct field used to have no way to
+        /// recurse into its own visit_struct call, and the outermost struct wrapped everything in
+        /// one section named after the Rust type instead of leaving that to its nested fields.
+        #[test]
+        fn serializer_emits_one_section_per_nested_struct () {
+            let config = Config { db: Db { host: "x".to_string() } };
+            let mut serializer = Serializer::new();
+
+            match config.serialize(&mut serializer) {
+                Ok(()) => (),
+                Err(_) => panic!("expected serialize to succeed"),
+            }
+
+            let ini_file = serializer.into_ini_file();
+            assert_eq!(ini_file.len(), 1);
+
+            match ini_file[0] {
+                Entry::Section(ref section) => {
+                    assert_eq!(section.name.as_slice(), "Db");
+                    match section.entries[0] {
+                        SectionEntry::Key(ref name, ref value) => {
+                            assert_eq!(name.as_slice(), "host");
+                            assert_eq!(value.as_slice(), "x");
+                        },
+                        _ => panic!("expected a key entry"),
+                    }
+                },
+                _ => panic!("expected a section entry"),
+            }
+        }
+    }
+}
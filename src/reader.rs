@@ -4,17 +4,21 @@
 ///
 /// grammar:
 ///
-/// ini          -> comment* section*
+/// ini          -> (comment | directive | key)* section*
 /// comment      -> ';' non_newline* '\n'
+/// directive    -> '@include' '"' non_quote+ '"' '\n'
 /// section      -> section_name (comment | key)*
 /// section_name -> '[' non_newline+ ']' '\n'
 /// key          -> non_newline+ '=' non_newline+ '\n'
 
 pub mod reader {
     use std::char::Char;
+    use std::collections::HashSet;
+    use std::iter::range;
     use std::io::fs::File;
-    use std::io::BufferedReader;
+    use std::io::{BufferedReader, IoResult, MemReader, Reader};
     use std::path::windows::Path;
+    use std::path::GenericPath;
 
     use ini_format::ini_format::IniFile;
     use ini_format::ini_format::Entry;
@@ -45,73 +49,249 @@ pub mod reader {
         SectionName(String),
         Key(String, String),
         Comment(String),
+        Entries(IniFile),
     }
 
+    /// Recursive @include is bounded so a cycle that slips past `included_paths` (e.g. via
+    /// symlinks) can't recurse forever.
+    static MAX_INCLUDE_DEPTH: uint = 32;
+
     enum MatchResult {
         Ok,
-        Err(String),
+        Err(Error),
     }
 
     pub enum ParseResult {
         Ok,
         StepOk(IniItem),
-        Err(String),
+        Err(Error),
     }
 
     enum ReadResult {
         Ok(String),
-        Err(String),
+        Err(Error),
+    }
+
+    /// A parse failure, carrying the byte offset, line and column at which it was detected along
+    /// with the text of the offending line as scanned up to that point, so callers can render a
+    /// caret-underlined snippet instead of a bare message.
+    pub struct Error {
+        pub message: String,
+        pub offset: uint,
+        pub line: uint,
+        pub column: uint,
+        line_text: String,
+    }
+
+    impl Error {
+        /// Renders `message (line L, column C)` followed by the offending line (up to the point of
+        /// failure) and a caret
+        /// underneath the column where the failure was detected.
+        pub fn to_string (&self) -> String {
+            let mut caret = String::new();
+            for _ in range(1, self.column) {
+                caret.push(' ');
+            }
+            caret.push('^');
+
+            format!("{} (line {}, column {})\n{}\n{}", self.message, self.line, self.column,
+                    self.line_text, caret)
+        }
+    }
+
+    impl ::std::fmt::Show for Error {
+        fn fmt (&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{}", self.to_string())
+        }
+    }
+
+    /// One token scanned off the input stream. `IniReader`'s `Iterator` implementation yields
+    /// these lazily instead of requiring the whole file to be parsed up front.
+    pub enum Event {
+        Comment(String),
+        SectionStart(String),
+        Key { name: String, value: String },
+        Include(IniFile),
     }
 
-    pub struct IniReader {
-        reader: BufferedReader<File>,
+    /// Parses from anything implementing `Reader` — a `File`, a `MemReader` over an in-memory
+    /// string, a network stream, or a test fixture — rather than hard-coding a filesystem path.
+    pub struct IniReader<R> {
+        reader: BufferedReader<R>,
         next_char: char,
         pub ini: IniFile,
         good: bool,
+        started: bool,
+        base_dir: Path,
+        included_paths: HashSet<String>,
+        include_depth: uint,
+        list_mode: bool,
+        offset: uint,
+        line: uint,
+        column: uint,
+        line_buf: String,
     }
 
 
     //////////////////////////////////////////////////////////////////////////////////////////////
 
-    impl IniReader {
-        pub fn new (filename: String) -> IniReader {
-            let mut ini_reader = IniReader {
-                reader: BufferedReader::new(match File::open(&Path::new(filename)) {
-                        Ok(f)   => f,
-                        Err(e)  => panic!("File error: {}", e),
-                    }),
+    /// Collapses `.`/`..` components in a `/`- or `\`-separated path string, so that e.g.
+    /// `a/b/../c` and `a/c` are recognized as the same file by `included_paths` instead of
+    /// evading cycle detection and only getting caught by `MAX_INCLUDE_DEPTH` instead.
+    fn normalize_path_string (path: &str) -> String {
+        let slashed = path.replace("\\", "/");
+        let mut components = Vec::<&str>::new();
+
+        for part in slashed.as_slice().split('/') {
+            match part {
+                ""   => (),
+                "."  => (),
+                ".." => { components.pop(); },
+                _    => components.push(part),
+            }
+        }
+
+        components.connect("/")
+    }
+
+    impl IniReader<File> {
+        /// Opens `filename` and returns a reader over it, or the `IoError` if it couldn't be
+        /// opened — unlike the old panicking constructor, this lets callers handle a missing or
+        /// unreadable file themselves.
+        pub fn from_file (filename: String) -> IoResult<IniReader<File>> {
+            let path = Path::new(filename.clone());
+            let file = try!(File::open(&path));
+
+            let mut included_paths = HashSet::<String>::new();
+            included_paths.insert(normalize_path_string(filename.as_slice()));
+
+            Ok(IniReader::new(BufferedReader::new(file), path.dir_path(), included_paths, 0))
+        }
+
+        /// Opens `filename` as a nested reader for an `@include`, inheriting the including
+        /// reader's cycle-detection set, depth counter and list mode instead of starting fresh.
+        fn new_include (filename: String, included_paths: HashSet<String>, include_depth: uint, list_mode: bool)
+            -> Result<IniReader<File>, String> {
+            let path = Path::new(filename.clone());
+            let file = match File::open(&path) {
+                Ok(f)  => f,
+                Err(e) => return Err(format!("could not open included file '{}': {}", filename, e)),
+            };
+
+            let mut reader = IniReader::new(BufferedReader::new(file), path.dir_path(), included_paths, include_depth);
+            reader.list_mode = list_mode;
+            Ok(reader)
+        }
+    }
+
+    impl IniReader<MemReader> {
+        /// Parses `text` as an in-memory INI document; the document itself is read from memory,
+        /// though an `@include` directive still opens its file from disk, resolved relative to the
+        /// current working directory, since there is no source file to anchor it to.
+        pub fn from_str (text: &str) -> IniReader<MemReader> {
+            let reader = BufferedReader::new(MemReader::new(text.as_bytes().to_vec()));
+            IniReader::new(reader, Path::new("."), HashSet::<String>::new(), 0)
+        }
+    }
+
+    impl<R: Reader> IniReader<R> {
+        /// Parses from any `Reader`. An `@include` directive resolves relative to the current
+        /// working directory, since there is no source file to anchor it to.
+        pub fn from_reader (r: R) -> IniReader<R> {
+            IniReader::new(BufferedReader::new(r), Path::new("."), HashSet::<String>::new(), 0)
+        }
+
+        fn new (reader: BufferedReader<R>, base_dir: Path, included_paths: HashSet<String>, include_depth: uint)
+            -> IniReader<R> {
+            IniReader {
+                reader: reader,
                 next_char: ' ',
                 ini: Vec::<Entry>::new(),
                 good: true,
-            };
+                started: false,
+                base_dir: base_dir,
+                included_paths: included_paths,
+                include_depth: include_depth,
+                list_mode: false,
+                offset: 0,
+                line: 1,
+                column: 0,
+                line_buf: String::new(),
+            }
+        }
 
-            ini_reader
+        /// Builds an `Error` anchored at the reader's current offset/line/column, attaching the
+        /// text scanned so far on the current line.
+        fn make_error (&self, message: String) -> Error {
+            Error {
+                message: message,
+                offset: self.offset,
+                line: self.line,
+                column: self.column,
+                line_text: self.line_buf.clone(),
+            }
+        }
+
+        /// When enabled, a key repeated within the same section is collapsed into a single
+        /// bracketed list value (`(a, b, c)`) instead of overwriting/duplicating, and a bare
+        /// comma-separated value is normalized into the same bracketed form. Use `parse_list` to
+        /// read such a value back out as a `Vec<String>`.
+        pub fn set_list_mode (&mut self, enabled: bool) {
+            self.list_mode = enabled;
         }
 
 
         //////////////////////////////////////////////////////////////////////////////////////////////
         // Parser functions
 
+        /// Folds the event stream back into a full `IniFile`, for callers that want the whole
+        /// tree rather than processing it incrementally.
         pub fn parse (&mut self) -> ParseResult {
-            let mut result;
-
-            self.get_next_char();
-
-            while self.good {
-                if self.next_char == ';' {
-                    result = self.parse_comment(true);
-                } else {
-                    result = self.parse_section();
+            let mut current_section: Option<Section> = None;
+
+            loop {
+                match self.next() {
+                    None => break,
+                    Some(Err(e)) => return ParseResult::Err(e),
+                    Some(Ok(Event::Comment(comment))) => {
+                        match current_section {
+                            Some(ref mut section) => section.entries.push(SectionEntry::Comment(comment)),
+                            None => self.ini.push(Entry::Comment(comment)),
+                        }
+                    },
+                    Some(Ok(Event::SectionStart(name))) => {
+                        match current_section.take() {
+                            Some(section) => self.ini.push(Entry::Section(section)),
+                            None => (),
+                        }
+                        current_section = Some(Section { name: name, entries: Vec::<SectionEntry>::new() });
+                    },
+                    Some(Ok(Event::Key { name, value })) => {
+                        match current_section {
+                            Some(ref mut section) => store_key(section, name, value, self.list_mode),
+                            None => {
+                                // A key before any `[section]` header is gathered into a
+                                // synthetic, unnamed section rather than rejected, so callers
+                                // like ini_serde's DEFAULT_SECTION can round-trip it.
+                                let mut section = Section { name: String::new(), entries: Vec::<SectionEntry>::new() };
+                                store_key(&mut section, name, value, self.list_mode);
+                                current_section = Some(section);
+                            },
+                        }
+                    },
+                    Some(Ok(Event::Include(included_entries))) => {
+                        match current_section.take() {
+                            Some(section) => self.ini.push(Entry::Section(section)),
+                            None => (),
+                        }
+                        self.ini.extend(included_entries.into_iter());
+                    },
                 }
+            }
 
-                match result {
-                   ParseResult::Err(e) => { return ParseResult::Err(e); },
-                   ParseResult::StepOk(item) => match item {
-                    IniItem::Entry(entry) => self.ini.push(entry),
-                    _ => (),
-                   },
-                   _ => (),
-                }
+            match current_section.take() {
+                Some(section) => self.ini.push(Entry::Section(section)),
+                None => (),
             }
 
             ParseResult::Ok
@@ -137,49 +317,6 @@ pub mod reader {
 
         //////////////////////////////////////////////////////////////////////////////////////////////
 
-        fn parse_section (&mut self) -> ParseResult {
-            let mut result;
-
-            result = self.parse_section_name();
-            let mut section_name = String::new();
-            match result {
-                ParseResult::Err(e) => { return ParseResult::Err(e); },
-                ParseResult::StepOk(item) => match item {
-                    IniItem::SectionName(name) => section_name = name,
-                    _ => (),
-                },
-                _ => (),
-            };
-
-            let mut section = Section { name: section_name, entries: Vec::<SectionEntry>::new() };
-
-            while self.good {
-                if self.next_char == ';' {
-                    result = self.parse_comment(false);
-                } else if self.next_char == '[' {
-                    break;
-                } else {
-                    result = self.parse_key();
-                }
-
-                match result {
-                    ParseResult::Err(e) => return ParseResult::Err(e),
-                    ParseResult::StepOk(item) => match item {
-                        IniItem::Comment(comment) =>
-                            section.entries.push(SectionEntry::Comment(comment)),
-                        IniItem::Key(name, value) =>
-                            section.entries.push(SectionEntry::Key(name, value)),
-                        _ => (),
-                    },
-                    _ => (),
-                };
-            }
-            
-            ParseResult::StepOk(IniItem::Entry(Entry::Section(section)))
-        }
-
-        //////////////////////////////////////////////////////////////////////////////////////////////
-
         fn parse_section_name (&mut self) -> ParseResult {
             verify_match!(self.match_token('[', "parse_section"));
 
@@ -214,17 +351,105 @@ pub mod reader {
             ParseResult::StepOk(IniItem::Key(key_name, key_value))
         }
 
+        //////////////////////////////////////////////////////////////////////////////////////////////
+
+        /// Parses a root-level `@include "path/to/file.ini"` directive and splices the referenced
+        /// file's entries in at this position.
+        fn parse_directive (&mut self) -> ParseResult {
+            verify_match!(self.match_token('@', "parse_directive"));
+
+            let mut directive = String::new();
+            while self.good && self.next_char != '"' {
+                directive.push(self.next_char);
+                self.get_next_char();
+            }
+            directive = directive.trim().to_string();
+
+            if directive.as_slice() != "include" {
+                return ParseResult::Err(self.make_error(format!("unknown directive '@{}'", directive)));
+            }
+
+            if !self.good {
+                return ParseResult::Err(self.make_error("malformed @include: missing filename".to_string()));
+            }
+
+            verify_match!(self.match_token('"', "parse_directive"));
+
+            let mut include_path = String::new();
+            while self.good && self.next_char != '"' {
+                include_path.push(self.next_char);
+                self.get_next_char();
+            }
+
+            if !self.good {
+                return ParseResult::Err(self.make_error("malformed @include: unterminated '\"'".to_string()));
+            }
+
+            verify_match!(self.match_token('"', "parse_directive"));
+
+            if include_path.is_empty() {
+                return ParseResult::Err(self.make_error("malformed @include: empty filename".to_string()));
+            }
+
+            match self.resolve_include(include_path.as_slice()) {
+                Ok(entries) => ParseResult::StepOk(IniItem::Entries(entries)),
+                Err(e)      => ParseResult::Err(e),
+            }
+        }
+
+        /// Resolves `relative_path` against this reader's directory, parses it with a nested
+        /// `IniReader`, and returns its entries. Rejects cycles and over-deep include chains.
+        fn resolve_include (&mut self, relative_path: &str) -> Result<IniFile, Error> {
+            if self.include_depth >= MAX_INCLUDE_DEPTH {
+                return Err(self.make_error(
+                    format!("@include nesting exceeds {} levels, aborting", MAX_INCLUDE_DEPTH)));
+            }
+
+            let resolved = self.base_dir.join(relative_path);
+            let resolved_name = match resolved.as_str() {
+                Some(s) => normalize_path_string(s),
+                None    => return Err(self.make_error(
+                    format!("@include path '{}' is not valid UTF-8", relative_path))),
+            };
+
+            if self.included_paths.contains(&resolved_name) {
+                return Err(self.make_error(
+                    format!("@include cycle detected: '{}' is already being parsed", resolved_name)));
+            }
+
+            let mut included_paths = self.included_paths.clone();
+            included_paths.insert(resolved_name.clone());
+
+            let mut nested = match IniReader::<File>::new_include(resolved_name.clone(), included_paths, self.include_depth + 1, self.list_mode) {
+                Ok(reader) => reader,
+                Err(e)     => return Err(self.make_error(e)),
+            };
+
+            match nested.parse() {
+                ParseResult::Ok        => Ok(nested.ini),
+                ParseResult::Err(e)    => Err(self.make_error(
+                    format!("in included file '{}': {}", resolved_name, e.to_string()))),
+                ParseResult::StepOk(_) => Err(self.make_error(
+                    format!("in included file '{}': unexpected partial parse", resolved_name))),
+            }
+        }
+
 
         //////////////////////////////////////////////////////////////////////////////////////////////
         // Helper functions
 
         fn match_token (&mut self, match_char: char, fun: &str) -> MatchResult {
+            if !self.good {
+                return MatchResult::Err(self.make_error(
+                    format!("in {}: unexpected end of file, expected '{}'", fun, match_char)));
+            }
+
             if self.next_char != match_char {
-                return MatchResult::Err(format!("In {}: expected '{}', got '{}'", fun,
-                                                match_char, self.next_char));
+                return MatchResult::Err(self.make_error(
+                    format!("in {}: expected '{}', got '{}'", fun, match_char, self.next_char)));
             }
 
-            self.get_next_char();            
+            self.get_next_char();
             MatchResult::Ok
         }
 
@@ -237,6 +462,17 @@ pub mod reader {
                                     self.good = false;
                                     return;
                                }
+                };
+
+                self.offset += self.next_char.len_utf8();
+
+                if self.next_char == '\n' {
+                    self.line += 1;
+                    self.column = 0;
+                    self.line_buf.clear();
+                } else {
+                    self.column += 1;
+                    self.line_buf.push(self.next_char);
                 }
             }
         }
@@ -247,14 +483,243 @@ pub mod reader {
                 self.get_next_char();
             }
 
+            if !self.good {
+                return ReadResult::Err(self.make_error("unexpected end of file".to_string()));
+            }
+
             let line = match self.reader.read_line() {
                     Ok(str) => str,
-                    Err(e) => return ReadResult::Err(format!("IO error: {}", e))
+                    Err(e) => return ReadResult::Err(self.make_error(format!("IO error: {}", e)))
             };
+            for c in line.as_slice().chars() {
+                self.offset += c.len_utf8();
+                if c == '\n' {
+                    self.line += 1;
+                    self.column = 0;
+                    self.line_buf.clear();
+                } else {
+                    self.column += 1;
+                    self.line_buf.push(c);
+                }
+            }
             let out = format!("{}{}", self.next_char, line);
 
             self.get_next_char();
             ReadResult::Ok(out)
         }
     }
+
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // List-key support, shared by the reader (to collapse repeated/comma keys) and the writer
+    // (to expand a bracketed value back out to repeated keys).
+
+    /// Pushes `name = value` into `section`, honoring `list_mode`: a key repeated within the
+    /// section is merged into a single bracketed list value instead of duplicated, and a bare
+    /// comma-separated value is normalized into the same bracketed form on first sight.
+    fn store_key (section: &mut Section, name: String, value: String, list_mode: bool) {
+        if list_mode {
+            for sub_entry in section.entries.iter_mut() {
+                match sub_entry {
+                    &mut SectionEntry::Key(ref existing_name, ref mut existing_value)
+                        if existing_name.as_slice() == name.as_slice() => {
+                        let mut values = parse_list(existing_value.as_slice());
+                        values.push(value);
+                        *existing_value = format_list(&values);
+                        return;
+                    },
+                    _ => (),
+                }
+            }
+
+            if value.as_slice().contains(",") && !value.as_slice().trim().starts_with("(") {
+                section.entries.push(SectionEntry::Key(name, format!("({})", value.trim())));
+                return;
+            }
+        }
+
+        section.entries.push(SectionEntry::Key(name, value));
+    }
+
+    /// Renders a list of values as the bracketed inline list syntax, e.g. `(a, b, c)`.
+    pub fn format_list (values: &Vec<String>) -> String {
+        let mut out = String::new();
+        out.push_str("(");
+
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(value.as_slice());
+        }
+
+        out.push_str(")");
+        out
+    }
+
+    /// Splits a key's raw value into a list: a `(a, b, c)` bracketed literal, a bare
+    /// comma-separated list, or a bare whitespace-separated list, in that order of preference.
+    /// A scalar value with none of these shapes comes back as a single-element list.
+    pub fn parse_list (value: &str) -> Vec<String> {
+        let trimmed = value.trim();
+
+        let inner = if trimmed.starts_with("(") && trimmed.ends_with(")") {
+            trimmed.slice(1, trimmed.len() - 1)
+        } else {
+            trimmed
+        };
+
+        if inner.contains(",") {
+            inner.split(',').map(|piece| piece.trim().to_string()).collect()
+        } else {
+            inner.split(' ').filter(|piece| !piece.is_empty()).map(|piece| piece.trim().to_string()).collect()
+        }
+    }
+
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // Pull-based event stream, sharing the parse_comment/parse_section_name/parse_key primitives
+    // above so parse() can stay a thin fold over the same tokens.
+
+    impl<R: Reader> Iterator for IniReader<R> {
+        type Item = Result<Event, Error>;
+
+        fn next (&mut self) -> Option<Result<Event, Error>> {
+            if !self.started {
+                self.started = true;
+                self.get_next_char();
+            }
+
+            if !self.good {
+                return None;
+            }
+
+            let result = if self.next_char == ';' {
+                self.parse_comment(true)
+            } else if self.next_char == '[' {
+                self.parse_section_name()
+            } else if self.next_char == '@' {
+                self.parse_directive()
+            } else {
+                self.parse_key()
+            };
+
+            match result {
+                ParseResult::Err(e) => Some(Err(e)),
+                ParseResult::StepOk(IniItem::Entry(Entry::Comment(comment))) => Some(Ok(Event::Comment(comment))),
+                ParseResult::StepOk(IniItem::SectionName(name)) => Some(Ok(Event::SectionStart(name))),
+                ParseResult::StepOk(IniItem::Key(name, value)) => Some(Ok(Event::Key { name: name, value: value })),
+                ParseResult::StepOk(IniItem::Entries(entries)) => Some(Ok(Event::Include(entries))),
+                _ => Some(Err(self.make_error("unexpected token while scanning event stream".to_string()))),
+            }
+        }
+    }
+
+
+    //////////////////////////////////////////////////////////////////////////////////////////////
+    // Apart from from_file's missing-file test, tests exercise IniReader through
+    // from_str/from_reader rather than touching the filesystem; see normalize_path_string's own test for the cycle-detection fix itself, since exercising
+    // resolve_include end to end would need real files on disk.
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn list_mode_merges_repeated_keys () {
+            let mut ini_reader = IniReader::from_str("[section]\nkey = a\nkey = b\n");
+            ini_reader.set_list_mode(true);
+
+            match ini_reader.parse() {
+                ParseResult::Ok => (),
+                _ => panic!("expected a successful parse"),
+            }
+
+            match ini_reader.ini[0] {
+                Entry::Section(ref section) => match section.entries[0] {
+                    SectionEntry::Key(ref name, ref value) => {
+                        assert_eq!(name.as_slice(), "key");
+                        assert_eq!(value.as_slice(), "(a, b)");
+                    },
+                    _ => panic!("expected a key entry"),
+                },
+                _ => panic!("expected a section entry"),
+            }
+        }
+
+        #[test]
+        fn error_rendering_includes_caret_underline () {
+            let mut ini_reader = IniReader::from_str("@bogus \"x\"\n");
+
+            let error = match ini_reader.parse() {
+                ParseResult::Err(e) => e,
+                _ => panic!("expected a parse error"),
+            };
+
+            let rendered = error.to_string();
+            assert!(rendered.as_slice().contains("unknown directive"));
+            assert!(rendered.as_slice().contains("^"));
+            assert_eq!(error.line, 1);
+        }
+
+        #[test]
+        fn normalize_path_string_collapses_dot_components () {
+            assert_eq!(normalize_path_string("a/./b/../c").as_slice(), "a/c");
+            assert_eq!(normalize_path_string("a\\b\\..\\c").as_slice(), "a/c");
+            assert_eq!(normalize_path_string("./a.ini").as_slice(), "a.ini");
+        }
+
+        /// Covers chunk0-7's own deliverable: parsing from an arbitrary `Reader`, not just the
+        /// `MemReader` that `from_str` happens to wrap.
+        #[test]
+        fn from_reader_parses_any_reader () {
+            let mut ini_reader = IniReader::from_reader(MemReader::new(
+                "[section]\nkey = value\n".as_bytes().to_vec()));
+
+            match ini_reader.parse() {
+                ParseResult::Ok => (),
+                _ => panic!("expected a successful parse"),
+            }
+
+            match ini_reader.ini[0] {
+                Entry::Section(ref section) => assert_eq!(section.name.as_slice(), "section"),
+                _ => panic!("expected a section entry"),
+            }
+        }
+
+        /// Covers chunk0-7's other deliverable: from_file returns an IoResult instead of
+        /// panicking, so a missing file is a caller-handleable Err.
+        #[test]
+        fn from_file_returns_err_for_a_missing_file () {
+            match IniReader::from_file("/no/such/path/does-not-exist.ini".to_string()) {
+                Err(_) => (),
+                Ok(_)  => panic!("expected an IoError for a missing file"),
+            }
+        }
+
+        /// Covers chunk0-2's Event iterator directly, rather than only through parse()'s fold
+        /// over it.
+        #[test]
+        fn iterator_yields_events_directly () {
+            let mut ini_reader = IniReader::from_str("[section]\nkey = value\n");
+
+            match ini_reader.next() {
+                Some(Ok(Event::SectionStart(ref name))) => assert_eq!(name.as_slice(), "section"),
+                _ => panic!("expected a SectionStart event"),
+            }
+
+            match ini_reader.next() {
+                Some(Ok(Event::Key { ref name, ref value })) => {
+                    assert_eq!(name.as_slice(), "key");
+                    assert_eq!(value.as_slice(), "value");
+                },
+                _ => panic!("expected a Key event"),
+            }
+
+            match ini_reader.next() {
+                None => (),
+                _ => panic!("expected end of stream"),
+            }
+        }
+    }
 }
\ No newline at end of file
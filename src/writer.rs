@@ -7,26 +7,68 @@ pub mod writer {
     use ini_format::ini_format::SectionEntry;
     use ini_format::ini_format::Section;
 
+    use reader::reader::parse_list;
+
     pub fn write_ini_file (ini_file: Vec<Entry>) {
+        print!("{}", write_ini_string(&ini_file));
+    }
+
+    /// Renders an `IniFile` to a `String` rather than printing it, so callers (e.g. the serde
+    /// `Serializer`) can capture the output instead of sending it to stdout. List values are
+    /// written back out in their stored bracketed form; see `write_ini_string_expanding_lists`
+    /// to expand them to repeated keys instead.
+    pub fn write_ini_string (ini_file: &Vec<Entry>) -> String {
+        write_ini_string_with_options(ini_file, false)
+    }
+
+    /// Like `write_ini_string`, but a bracketed `(a, b, c)` list value is written back out as
+    /// repeated `key = value` lines rather than a single bracketed line.
+    pub fn write_ini_string_expanding_lists (ini_file: &Vec<Entry>) -> String {
+        write_ini_string_with_options(ini_file, true)
+    }
+
+    fn write_ini_string_with_options (ini_file: &Vec<Entry>, expand_lists: bool) -> String {
+        let mut out = String::new();
+
         for entry in ini_file.iter() {
             match entry {
                 &Entry::Section(ref section) => {
-                                                    write_section(section);
-                                                    println!("");
+                                                    write_section(&mut out, section, expand_lists);
+                                                    out.push_str("\n");
                                                 },
-                &Entry::Comment(ref comment) => println!("; {}", comment),
+                &Entry::Comment(ref comment) => out.push_str(format!("; {}\n", comment).as_slice()),
             }
         }
+
+        out
     }
 
-    fn write_section (section: &Section) {
-        println!("[{}]", section.name);
+    fn write_section (out: &mut String, section: &Section, expand_lists: bool) {
+        // A section with an empty name is the synthetic "keys before any [section] header"
+        // section the reader produces for root-level keys (see reader::parse); writing out a
+        // literal "[]" header for it would be a line that never existed in the source text.
+        if !section.name.is_empty() {
+            out.push_str(format!("[{}]\n", section.name).as_slice());
+        }
 
         for sub_entry in section.entries.iter() {
             match sub_entry {
-                &SectionEntry::Comment(ref comment)     => println!("; {}", comment),
-                &SectionEntry::Key(ref name, ref value) => println!("{} = {}", name, value),
+                &SectionEntry::Comment(ref comment) => out.push_str(format!("; {}\n", comment).as_slice()),
+                &SectionEntry::Key(ref name, ref value) => {
+                    if expand_lists && is_list_value(value.as_slice()) {
+                        for item in parse_list(value.as_slice()).iter() {
+                            out.push_str(format!("{} = {}\n", name, item).as_slice());
+                        }
+                    } else {
+                        out.push_str(format!("{} = {}\n", name, value).as_slice());
+                    }
+                },
             }
         }
     }
-}
\ No newline at end of file
+
+    fn is_list_value (value: &str) -> bool {
+        let trimmed = value.trim();
+        trimmed.starts_with("(") && trimmed.ends_with(")")
+    }
+}